@@ -117,16 +117,57 @@ impl ZcashDeserialize for OutPoint {
     }
 }
 
+/// Marker describing the authorization state of a [`Bundle`].
+///
+/// This mirrors librustzcash's `Bundle<A: Authorization>`: the associated
+/// [`Witness`](Authorization::Witness) type selects what each [`TzeIn`] carries.
+/// An [`Unauthorized`] bundle has preconditions and outputs but no witnesses
+/// yet; an [`Authorized`] bundle has every witness populated and validated.
+pub trait Authorization {
+    /// The witness carried by each [`TzeIn`] in this authorization state.
+    type Witness;
+}
+
+/// Authorization state of a fully witnessed bundle, ready to be serialized.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Authorized;
+
+impl Authorization for Authorized {
+    type Witness = Data;
+}
+
+/// Authorization state of a bundle whose witnesses have not been built yet.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Unauthorized;
+
+impl Authorization for Unauthorized {
+    type Witness = ();
+}
+
+/// Transforms the witnesses and authorization of a [`Bundle`] from state `A` to
+/// state `B` in a single pass, following librustzcash's `MapAuth`.
+pub trait MapAuth<A: Authorization, B: Authorization> {
+    /// Maps a single input witness.
+    fn map_witness(&self, witness: A::Witness) -> B::Witness;
+    /// Maps the bundle-level authorization.
+    fn map_authorization(&self, authorization: A) -> B;
+}
+
 /// Witness data used to satisfy a previously published precondition.
+///
+/// The witness type `W` is supplied by the bundle's
+/// [`Authorization::Witness`]: [`Data`] for an [`Authorized`] bundle, `()` for
+/// an [`Unauthorized`] one. It defaults to [`Data`] so existing consumers that
+/// name the bare `TzeIn` keep referring to the authorized form.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TzeIn {
+pub struct TzeIn<W = Data> {
     /// Reference to the committed precondition.
     pub prevout: OutPoint,
     /// Witness payload to be evaluated by the extension.
-    pub witness: Data,
+    pub witness: W,
 }
 
-impl fmt::Display for TzeIn {
+impl<W: fmt::Debug> fmt::Display for TzeIn<W> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("tze::TzeIn")
             .field("prevout", &self.prevout)
@@ -135,14 +176,14 @@ impl fmt::Display for TzeIn {
     }
 }
 
-impl ZcashSerialize for TzeIn {
+impl ZcashSerialize for TzeIn<Data> {
     fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
         self.prevout.zcash_serialize(&mut writer)?;
         self.witness.zcash_serialize(&mut writer)
     }
 }
 
-impl ZcashDeserialize for TzeIn {
+impl ZcashDeserialize for TzeIn<Data> {
     fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
         Ok(Self {
             prevout: OutPoint::zcash_deserialize(&mut reader)?,
@@ -188,15 +229,41 @@ impl ZcashDeserialize for TzeOut {
 }
 
 /// Collection of TZE inputs and outputs embedded in a transaction.
+///
+/// The type parameter `A` records whether the bundle's witnesses have been
+/// built: only an [`Authorized`] bundle can be serialized. It defaults to
+/// [`Authorized`] so existing consumers that name the bare `Bundle` keep
+/// referring to the serializable form.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Bundle {
+pub struct Bundle<A: Authorization = Authorized> {
     /// Witnesses spending prior TZE outputs.
-    pub inputs: Vec<TzeIn>,
+    pub inputs: Vec<TzeIn<A::Witness>>,
     /// Newly created TZE outputs.
     pub outputs: Vec<TzeOut>,
+    /// Bundle-level authorization state.
+    pub authorization: A,
+}
+
+impl<A: Authorization> Bundle<A> {
+    /// Transforms every input witness and the bundle-level authorization from
+    /// state `A` to state `B` in a single pass.
+    pub fn map_authorization<B: Authorization, F: MapAuth<A, B>>(self, f: F) -> Bundle<B> {
+        Bundle {
+            inputs: self
+                .inputs
+                .into_iter()
+                .map(|input| TzeIn {
+                    prevout: input.prevout,
+                    witness: f.map_witness(input.witness),
+                })
+                .collect(),
+            outputs: self.outputs,
+            authorization: f.map_authorization(self.authorization),
+        }
+    }
 }
 
-impl fmt::Display for Bundle {
+impl<A: Authorization> fmt::Display for Bundle<A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("tze::Bundle")
             .field("inputs", &self.inputs.len())
@@ -205,23 +272,24 @@ impl fmt::Display for Bundle {
     }
 }
 
-impl ZcashSerialize for Bundle {
+impl ZcashSerialize for Bundle<Authorized> {
     fn zcash_serialize<W: io::Write>(&self, mut writer: W) -> Result<(), io::Error> {
         self.inputs.zcash_serialize(&mut writer)?;
         self.outputs.zcash_serialize(&mut writer)
     }
 }
 
-impl ZcashDeserialize for Bundle {
+impl ZcashDeserialize for Bundle<Authorized> {
     fn zcash_deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
         Ok(Self {
             inputs: Vec::zcash_deserialize(&mut reader)?,
             outputs: Vec::zcash_deserialize(&mut reader)?,
+            authorization: Authorized,
         })
     }
 }
 
-impl TrustedPreallocate for TzeIn {
+impl TrustedPreallocate for TzeIn<Data> {
     fn max_allocation() -> u64 {
         MAX_BLOCK_BYTES / MIN_TZE_INPUT_SIZE
     }
@@ -233,13 +301,13 @@ impl TrustedPreallocate for TzeOut {
     }
 }
 
-impl TrustedPreallocate for Bundle {
+impl TrustedPreallocate for Bundle<Authorized> {
     fn max_allocation() -> u64 {
         1
     }
 }
 
-/// Convenience helper to produce an empty bundle.
-pub fn empty_bundle() -> Bundle {
+/// Convenience helper to produce an empty authorized bundle.
+pub fn empty_bundle() -> Bundle<Authorized> {
     Bundle::default()
 }
@@ -1,6 +1,9 @@
 //! Prototype STWO-Cairo Transparent Zcash Extension verifier.
 //!
-//! This crate exposes minimal constants and a stub verifier that always succeeds.
+//! This crate is the first implementation of the generic [`Extension`] trait
+//! from `zebra-tze`: [`StwoCairoExtension`] decodes the TZE precondition/witness
+//! payloads into typed values and verifies a Cairo STARK proof against them via
+//! [`verify_cairo`].
 
 use cairo_air::verifier::verify_cairo;
 use cairo_air::{CairoProof, PreProcessedTraceVariant};
@@ -10,9 +13,14 @@ use stwo_cairo_prover::stwo_prover::core::pcs::PcsConfig;
 use stwo_cairo_prover::stwo_prover::core::vcs::blake2_merkle::{
     Blake2sMerkleChannel, Blake2sMerkleHasher,
 };
+use std::io::Cursor;
+
 use thiserror::Error;
 use tracing::instrument;
-use zebra_chain::tze;
+use zebra_chain::serialization::{zcash_serialize_bytes, ZcashDeserialize};
+use zebra_chain::tze::{self, Mode};
+use zebra_tze::demo::{DemoExtension, DEMO_EXTENSION_ID};
+use zebra_tze::{Extension, FromPayload, Registry, ToPayload, VerifyContext};
 
 /// Extension identifier allocated to the STWO Cairo verifier TZE.
 ///
@@ -22,6 +30,15 @@ pub const STWO_CAIRO_EXTENSION_ID: u64 = 0x5354_574F;
 /// Supported TZE modes for the prototype.
 pub const STWO_CAIRO_SUPPORTED_MODES: &[u64] = &[0];
 
+/// Current payload version using the crate's length-prefixed binary framing.
+const STWO_PAYLOAD_VERSION: u8 = 1;
+
+/// Preprocessed-trace variant byte for a trace without the Pedersen builtin.
+const TRACE_WITHOUT_PEDERSEN: u8 = 0;
+
+/// Preprocessed-trace variant byte for the canonical trace (with Pedersen).
+const TRACE_WITH_PEDERSEN: u8 = 1;
+
 /// Errors that may be returned by the STWO verifier stub.
 #[derive(Debug, Error)]
 pub enum VerifyError {
@@ -31,12 +48,24 @@ pub enum VerifyError {
         /// The unsupported mode value.
         mode: u64,
     },
-    /// Returned when the precondition payload is malformed.
-    #[error("invalid precondition payload: {0}")]
-    InvalidPrecondition(&'static str),
-    /// Returned when witness data is not valid UTF-8.
-    #[error("invalid witness encoding: {0}")]
-    InvalidWitnessEncoding(&'static str),
+    /// Returned when a payload is malformed, naming where parsing failed.
+    #[error("malformed {kind} payload at offset {offset}: {reason}")]
+    MalformedPayload {
+        /// Which payload failed to parse (`"precondition"` or `"witness"`).
+        kind: &'static str,
+        /// Byte offset within the payload at which parsing failed.
+        offset: usize,
+        /// Human-readable reason for the failure.
+        reason: &'static str,
+    },
+    /// Returned when a payload declares a version this codec does not understand.
+    #[error("unsupported {kind} payload version {version}")]
+    UnsupportedVersion {
+        /// Which payload carried the version (`"precondition"` or `"witness"`).
+        kind: &'static str,
+        /// The unsupported version byte.
+        version: u8,
+    },
     /// Returned when the proof JSON payload cannot be parsed.
     #[error("invalid proof payload: {0}")]
     InvalidProof(#[from] SerdeJsonError),
@@ -45,10 +74,206 @@ pub enum VerifyError {
     VerificationFailed(String),
 }
 
+/// Typed mode-0 precondition: the preprocessed-trace variant to verify against.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StwoPrecondition {
+    /// Whether the Pedersen builtin is part of the preprocessed trace.
+    pub with_pedersen: bool,
+}
+
+/// Typed mode-0 witness: the Cairo STARK proof that satisfies the precondition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StwoWitness {
+    /// The Cairo proof to hand to the STWO verifier.
+    pub proof: CairoProof<Blake2sMerkleHasher>,
+}
+
+/// Rejects any mode outside [`STWO_CAIRO_SUPPORTED_MODES`].
+fn check_mode(mode: Mode) -> Result<(), VerifyError> {
+    if STWO_CAIRO_SUPPORTED_MODES.contains(&mode.0) {
+        Ok(())
+    } else {
+        Err(VerifyError::UnsupportedMode { mode: mode.0 })
+    }
+}
+
+/// Maps a precondition's preprocessed-trace variant byte to `with_pedersen`.
+fn trace_variant_with_pedersen(kind: &'static str, variant: u8) -> Result<bool, VerifyError> {
+    match variant {
+        TRACE_WITHOUT_PEDERSEN => Ok(false),
+        TRACE_WITH_PEDERSEN => Ok(true),
+        _ => Err(VerifyError::MalformedPayload {
+            kind,
+            offset: 1,
+            reason: "unknown preprocessed-trace variant",
+        }),
+    }
+}
+
+impl FromPayload for StwoPrecondition {
+    type Error = VerifyError;
+
+    fn from_payload(mode: Mode, payload: &[u8]) -> Result<Self, Self::Error> {
+        check_mode(mode)?;
+        // A single byte is the legacy precondition form: a bare `with_pedersen` flag.
+        if let [flag] = payload {
+            return Ok(StwoPrecondition {
+                with_pedersen: *flag != 0,
+            });
+        }
+        let (&version, rest) = payload.split_first().ok_or(VerifyError::MalformedPayload {
+            kind: "precondition",
+            offset: 0,
+            reason: "payload is empty",
+        })?;
+        if version != STWO_PAYLOAD_VERSION {
+            return Err(VerifyError::UnsupportedVersion {
+                kind: "precondition",
+                version,
+            });
+        }
+        match rest {
+            [variant] => Ok(StwoPrecondition {
+                with_pedersen: trace_variant_with_pedersen("precondition", *variant)?,
+            }),
+            [] => Err(VerifyError::MalformedPayload {
+                kind: "precondition",
+                offset: 1,
+                reason: "missing preprocessed-trace variant",
+            }),
+            _ => Err(VerifyError::MalformedPayload {
+                kind: "precondition",
+                offset: 2,
+                reason: "trailing bytes after preprocessed-trace variant",
+            }),
+        }
+    }
+}
+
+impl ToPayload for StwoPrecondition {
+    fn to_payload(&self) -> (Mode, Vec<u8>) {
+        let variant = if self.with_pedersen {
+            TRACE_WITH_PEDERSEN
+        } else {
+            TRACE_WITHOUT_PEDERSEN
+        };
+        (Mode(0), vec![STWO_PAYLOAD_VERSION, variant])
+    }
+}
+
+impl FromPayload for StwoWitness {
+    type Error = VerifyError;
+
+    fn from_payload(mode: Mode, payload: &[u8]) -> Result<Self, Self::Error> {
+        check_mode(mode)?;
+        // Legacy witnesses are a bare `serde_json` object, whose first byte is
+        // the `{` that opens the JSON `CairoProof`. The versioned framing below
+        // starts with a version byte instead, so the leading brace unambiguously
+        // selects the legacy path. This supersedes the original "legacy iff
+        // `version == 0`" plan: legacy payloads carry no version prefix at all,
+        // and a `0x00` first byte never occurs in the JSON they actually use.
+        if payload.first() == Some(&b'{') {
+            return Ok(StwoWitness {
+                proof: serde_json::from_slice(payload)?,
+            });
+        }
+        let (&version, rest) = payload.split_first().ok_or(VerifyError::MalformedPayload {
+            kind: "witness",
+            offset: 0,
+            reason: "payload is empty",
+        })?;
+        let proof_bytes = match version {
+            // Current: a CompactSize length-prefixed binary blob holding the proof.
+            STWO_PAYLOAD_VERSION => {
+                let mut cursor = Cursor::new(rest);
+                Vec::<u8>::zcash_deserialize(&mut cursor).map_err(|_| {
+                    VerifyError::MalformedPayload {
+                        kind: "witness",
+                        offset: 1,
+                        reason: "invalid length-prefixed proof blob",
+                    }
+                })?
+            }
+            _ => {
+                return Err(VerifyError::UnsupportedVersion {
+                    kind: "witness",
+                    version,
+                })
+            }
+        };
+        Ok(StwoWitness {
+            proof: serde_json::from_slice(&proof_bytes)?,
+        })
+    }
+}
+
+impl ToPayload for StwoWitness {
+    fn to_payload(&self) -> (Mode, Vec<u8>) {
+        // `CairoProof` round-trips through serde_json; the bytes are carried
+        // length-prefixed so the outer framing never relies on UTF-8.
+        let proof = serde_json::to_vec(&self.proof).expect("CairoProof serializes to JSON");
+        let mut payload = vec![STWO_PAYLOAD_VERSION];
+        zcash_serialize_bytes(&proof, &mut payload).expect("writing to a Vec is infallible");
+        (Mode(0), payload)
+    }
+}
+
+/// The STWO-Cairo transparent extension.
+///
+/// It decodes the typed payloads and verifies the Cairo STARK proof with
+/// [`verify_cairo`] against the preprocessed trace the precondition selects.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StwoCairoExtension;
+
+impl<C> Extension<C> for StwoCairoExtension {
+    type Precondition = StwoPrecondition;
+    type Witness = StwoWitness;
+    type Error = VerifyError;
+
+    fn verify_inner(
+        &self,
+        precondition: &Self::Precondition,
+        witness: &Self::Witness,
+        _context: &C,
+    ) -> Result<(), Self::Error> {
+        let preprocessed_trace = if precondition.with_pedersen {
+            PreProcessedTraceVariant::Canonical
+        } else {
+            PreProcessedTraceVariant::CanonicalWithoutPedersen
+        };
+
+        verify_cairo::<Blake2sMerkleChannel>(
+            witness.proof.clone(),
+            secure_pcs_config(),
+            preprocessed_trace,
+        )
+        .map_err(|err| VerifyError::VerificationFailed(format!("{err:?}")))
+    }
+}
+
+/// Builds a [`Registry`] with the STWO-Cairo and dual-hash-lock demo extensions
+/// registered.
+///
+/// The registry is parameterized by the consensus [`VerifyContext`], which the
+/// demo extension needs to reach sibling `TzeOut`s for mode chaining.
+pub fn registry<'a>() -> Registry<VerifyContext<'a>> {
+    let mut registry = Registry::new();
+    registry.register(
+        tze::ExtensionId(STWO_CAIRO_EXTENSION_ID),
+        Box::new(StwoCairoExtension),
+    );
+    registry.register(
+        tze::ExtensionId(DEMO_EXTENSION_ID),
+        Box::new(DemoExtension),
+    );
+    registry
+}
+
 /// Prototype verifier entry point.
 ///
-/// The current implementation does not execute a STARK verifier. It merely checks that the
-/// requested `(extension_id, mode)` pair matches the stub configuration and returns success.
+/// Decodes the precondition and witness payloads and dispatches through
+/// [`StwoCairoExtension`]. Retained for callers that verify a single
+/// `(precondition, witness)` pair outside the registry.
 #[instrument(level = "debug", skip(precondition, witness))]
 pub fn verify_stwo_cairo(
     extension_id: u64,
@@ -67,44 +292,9 @@ pub fn verify_stwo_cairo(
         );
     }
 
-    if !STWO_CAIRO_SUPPORTED_MODES.contains(&mode)
-        || !STWO_CAIRO_SUPPORTED_MODES.contains(&precondition.mode.0)
-        || !STWO_CAIRO_SUPPORTED_MODES.contains(&witness.mode.0)
-    {
-        return Err(VerifyError::UnsupportedMode { mode });
-    }
-
-    let (with_pedersen, proof_bytes) = extract_proof_bytes(precondition, witness)?;
-
-    let proof_str = std::str::from_utf8(proof_bytes)
-        .map_err(|_| VerifyError::InvalidWitnessEncoding("proof must be valid UTF-8"))?;
-    let cairo_proof: CairoProof<Blake2sMerkleHasher> = serde_json::from_str(proof_str)?;
-
-    let preprocessed_trace = if with_pedersen {
-        PreProcessedTraceVariant::Canonical
-    } else {
-        PreProcessedTraceVariant::CanonicalWithoutPedersen
-    };
-
-    verify_cairo::<Blake2sMerkleChannel>(cairo_proof, secure_pcs_config(), preprocessed_trace)
-        .map_err(|err| VerifyError::VerificationFailed(format!("{err:?}")))
-}
+    check_mode(Mode(mode))?;
 
-fn extract_proof_bytes<'a>(
-    precondition: &'a tze::Data,
-    witness: &'a tze::Data,
-) -> Result<(bool, &'a [u8]), VerifyError> {
-    if let Some(flag) = precondition.payload.first() {
-        let with_pedersen = *flag != 0;
-        Ok((with_pedersen, &witness.payload))
-    } else if let Some((flag, rest)) = witness.payload.split_first() {
-        let with_pedersen = *flag != 0;
-        Ok((with_pedersen, rest))
-    } else {
-        Err(VerifyError::InvalidPrecondition(
-            "witness payload must contain at least one byte",
-        ))
-    }
+    Extension::<()>::verify(&StwoCairoExtension, precondition, witness, &())
 }
 
 fn secure_pcs_config() -> PcsConfig {
@@ -117,3 +307,220 @@ fn secure_pcs_config() -> PcsConfig {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A freshly encoded `StwoPrecondition` decodes to an equal value, as
+    /// [`ToPayload`]'s round-trip guarantee requires, and re-encodes to the
+    /// same `(mode, payload)`.
+    #[test]
+    fn precondition_round_trips() {
+        for with_pedersen in [false, true] {
+            let precondition = StwoPrecondition { with_pedersen };
+            let (mode, payload) = precondition.to_payload();
+            let decoded = StwoPrecondition::from_payload(mode, &payload)
+                .expect("a freshly encoded precondition decodes");
+            assert_eq!(decoded, precondition);
+            assert_eq!(decoded.to_payload(), (mode, payload));
+        }
+    }
+
+    #[test]
+    fn precondition_rejects_unsupported_mode() {
+        let (_, payload) = StwoPrecondition { with_pedersen: true }.to_payload();
+        assert!(matches!(
+            StwoPrecondition::from_payload(Mode(7), &payload),
+            Err(VerifyError::UnsupportedMode { mode: 7 })
+        ));
+    }
+
+    /// A payload opening with `{` takes the legacy JSON path: it reaches proof
+    /// parsing (reported as [`VerifyError::InvalidProof`]) rather than being
+    /// rejected as an unknown version, which is what the pre-fix code did.
+    #[test]
+    fn witness_legacy_json_path_is_reached() {
+        assert!(matches!(
+            StwoWitness::from_payload(Mode(0), b"{not a real proof}"),
+            Err(VerifyError::InvalidProof(_))
+        ));
+    }
+
+    /// A version-1 payload unwraps its length-prefixed blob and hands the bytes
+    /// to the proof parser (here failing on the bytes, not on the framing).
+    #[test]
+    fn witness_versioned_binary_path_is_reached() {
+        let mut payload = vec![STWO_PAYLOAD_VERSION];
+        zcash_serialize_bytes(b"not a real proof", &mut payload).expect("Vec write is infallible");
+        assert!(matches!(
+            StwoWitness::from_payload(Mode(0), &payload),
+            Err(VerifyError::InvalidProof(_))
+        ));
+    }
+
+    /// A version-1 payload with a truncated length prefix fails on the framing.
+    #[test]
+    fn witness_versioned_malformed_framing_is_reported() {
+        assert!(matches!(
+            StwoWitness::from_payload(Mode(0), &[STWO_PAYLOAD_VERSION, 0xff]),
+            Err(VerifyError::MalformedPayload {
+                kind: "witness",
+                offset: 1,
+                ..
+            })
+        ));
+    }
+
+    /// A leading byte that is neither `{` nor a known version is rejected.
+    #[test]
+    fn witness_unknown_version_is_reported() {
+        assert!(matches!(
+            StwoWitness::from_payload(Mode(0), &[2, 0, 0]),
+            Err(VerifyError::UnsupportedVersion {
+                kind: "witness",
+                version: 2,
+            })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod consensus_tests {
+    use zebra_chain::{
+        amount::Amount,
+        block::Height,
+        parameters::ConsensusBranchId,
+        transaction,
+        tze::{Authorized, Bundle, Data, ExtensionId, Mode, OutPoint, TzeIn, TzeOut},
+    };
+    use zebra_tze::demo::{DemoExtension, Precondition, Witness, DEMO_EXTENSION_ID};
+    use zebra_tze::verify::Error;
+    use zebra_tze::{verify_bundle, ToPayload};
+
+    use crate::{registry, STWO_CAIRO_EXTENSION_ID};
+
+    const DEMO_ID: ExtensionId = ExtensionId(DEMO_EXTENSION_ID);
+
+    fn demo_out(value: i64, precondition: &Precondition) -> TzeOut {
+        TzeOut {
+            value: Amount::try_from(value).expect("value in range"),
+            precondition: precondition.to_data(DEMO_ID),
+        }
+    }
+
+    fn outpoint(index: u32) -> OutPoint {
+        OutPoint {
+            hash: transaction::Hash([0; 32]),
+            index,
+        }
+    }
+
+    fn input(witness: Data) -> TzeIn {
+        TzeIn {
+            prevout: outpoint(0),
+            witness,
+        }
+    }
+
+    /// Runs `bundle` against the stwo [`registry()`] with the given prevouts.
+    fn verify(bundle: &Bundle<Authorized>, prevouts: &[TzeOut]) -> Result<(), Error> {
+        verify_bundle(
+            bundle,
+            None,
+            prevouts,
+            ConsensusBranchId::default(),
+            Height(0),
+            &registry(),
+        )
+    }
+
+    /// A dual-hash-lock spend verifies end-to-end: the mode-0 input resolves
+    /// `hash_2` from the sibling mode-1 output and dispatches through the
+    /// registered [`DemoExtension`].
+    #[test]
+    fn dual_hash_lock_bundle_verifies() {
+        let preimage_1 = [1u8; 32];
+        let preimage_2 = [2u8; 32];
+        let hash_2 = DemoExtension::close_lock(&preimage_2);
+        let hash_1 = DemoExtension::open_lock(&preimage_1, &hash_2);
+
+        let bundle = Bundle {
+            inputs: vec![input(Witness::Open { preimage_1 }.to_data(DEMO_ID))],
+            outputs: vec![demo_out(0, &Precondition::Close { hash_2 })],
+            authorization: Authorized,
+        };
+        let prevouts = vec![demo_out(100, &Precondition::Open { hash_1 })];
+
+        assert!(verify(&bundle, &prevouts).is_ok());
+    }
+
+    #[test]
+    fn unknown_prevout_is_reported() {
+        let bundle = Bundle {
+            inputs: vec![input(Witness::Open { preimage_1: [0; 32] }.to_data(DEMO_ID))],
+            outputs: vec![],
+            authorization: Authorized,
+        };
+
+        assert!(matches!(verify(&bundle, &[]), Err(Error::UnknownPrevout { .. })));
+    }
+
+    #[test]
+    fn extension_mode_mismatch_is_reported() {
+        let witness = Witness::Open { preimage_1: [0; 32] }
+            .to_data(ExtensionId(STWO_CAIRO_EXTENSION_ID));
+        let bundle = Bundle {
+            inputs: vec![input(witness)],
+            outputs: vec![],
+            authorization: Authorized,
+        };
+        let prevouts = vec![demo_out(1, &Precondition::Open { hash_1: [0; 32] })];
+
+        assert!(matches!(verify(&bundle, &prevouts), Err(Error::Mismatch { .. })));
+    }
+
+    #[test]
+    fn unregistered_extension_is_reported() {
+        let data = Data {
+            extension_id: ExtensionId(0xDEAD),
+            mode: Mode(0),
+            payload: vec![0; 32],
+        };
+        let bundle = Bundle {
+            inputs: vec![input(data.clone())],
+            outputs: vec![],
+            authorization: Authorized,
+        };
+        let prevouts = vec![TzeOut {
+            value: Amount::try_from(1).expect("value in range"),
+            precondition: data,
+        }];
+
+        assert!(matches!(
+            verify(&bundle, &prevouts),
+            Err(Error::UnregisteredExtension { .. })
+        ));
+    }
+
+    #[test]
+    fn failing_witness_is_reported() {
+        let preimage_2 = [2u8; 32];
+        let hash_2 = DemoExtension::close_lock(&preimage_2);
+        let hash_1 = DemoExtension::open_lock(&[1u8; 32], &hash_2);
+
+        // A mode-1 output is present, so verification reaches the hash check and
+        // fails on the wrong preimage rather than MissingCloseOutput.
+        let bundle = Bundle {
+            inputs: vec![input(Witness::Open { preimage_1: [9; 32] }.to_data(DEMO_ID))],
+            outputs: vec![demo_out(0, &Precondition::Close { hash_2 })],
+            authorization: Authorized,
+        };
+        let prevouts = vec![demo_out(100, &Precondition::Open { hash_1 })];
+
+        assert!(matches!(
+            verify(&bundle, &prevouts),
+            Err(Error::VerificationFailed { .. })
+        ));
+    }
+}
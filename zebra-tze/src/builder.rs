@@ -0,0 +1,271 @@
+//! The [`ExtensionTxBuilder`] for assembling TZE inputs and outputs.
+
+use zebra_chain::{
+    amount::{self, Amount, NegativeAllowed, NonNegative},
+    tze::{Authorized, Bundle, Data, ExtensionId, Mode, OutPoint, TzeIn, TzeOut, Unauthorized},
+};
+
+use crate::extension::ToPayload;
+
+/// Errors that can occur while building a TZE bundle.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// A witness-building closure failed.
+    Witness(E),
+    /// The running value balance overflowed the valid money range.
+    Amount(amount::Error),
+    /// A witness closure produced [`Data`] whose `(extension_id, mode)` did not
+    /// match the pair declared to [`add_tze_input`](ExtensionTxBuilder::add_tze_input).
+    WitnessMismatch {
+        /// The `(extension_id, mode)` declared for the input.
+        declared: (ExtensionId, Mode),
+        /// The `(extension_id, mode)` stamped on the produced witness.
+        produced: (ExtensionId, Mode),
+    },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Witness(err) => write!(f, "witness builder failed: {err}"),
+            Error::Amount(err) => write!(f, "invalid value balance: {err}"),
+            Error::WitnessMismatch { declared, produced } => write!(
+                f,
+                "witness extension/mode {produced:?} does not match declared {declared:?}"
+            ),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for Error<E> {}
+
+/// Builder for the TZE portion of a transaction.
+///
+/// Inputs and outputs are accumulated into an [`Unauthorized`] [`Bundle`]; each
+/// input's witness is produced by a closure that is not invoked until
+/// [`build`](ExtensionTxBuilder::build), once every output is known. The closure
+/// receives the build context together with every accumulated [`TzeOut`], so
+/// chained extensions can reference a sibling output created in the same
+/// transaction when producing a witness, as the dual-hash-lock demo requires.
+///
+/// Follows librustzcash's `ExtensionTxBuilder`.
+pub struct ExtensionTxBuilder<'a, BuildCtx, E> {
+    build_ctx: &'a BuildCtx,
+    bundle: Bundle<Unauthorized>,
+    witness_builders: Vec<WitnessBuilder<'a, BuildCtx, E>>,
+    input_total: Amount<NonNegative>,
+    output_total: Amount<NonNegative>,
+}
+
+/// A deferred witness builder, paired with the `(extension_id, mode)` its output
+/// [`Data`] is expected to carry.
+struct WitnessBuilder<'a, BuildCtx, E> {
+    extension_id: ExtensionId,
+    mode: Mode,
+    build: Box<dyn FnOnce(&BuildCtx, &[TzeOut]) -> Result<Data, E> + 'a>,
+}
+
+impl<'a, BuildCtx, E> ExtensionTxBuilder<'a, BuildCtx, E> {
+    /// Returns a new builder that will evaluate witness closures against `build_ctx`.
+    pub fn new(build_ctx: &'a BuildCtx) -> Self {
+        ExtensionTxBuilder {
+            build_ctx,
+            bundle: Bundle::default(),
+            witness_builders: Vec::new(),
+            input_total: Amount::zero(),
+            output_total: Amount::zero(),
+        }
+    }
+
+    /// Adds a TZE input spending `prevout`, deferring witness construction to
+    /// `witness_builder`.
+    ///
+    /// `prevout` is the `(OutPoint, TzeOut)` pair identifying and describing the
+    /// output being spent; its value is added to the running input total. The
+    /// closure is invoked during [`build`](ExtensionTxBuilder::build), after
+    /// every output has been added, with the build context and the accumulated
+    /// outputs so it can reference a sibling [`TzeOut`]. The [`Data`] it returns
+    /// must carry `extension_id` and `mode`, or [`build`](ExtensionTxBuilder::build)
+    /// fails with [`Error::WitnessMismatch`].
+    pub fn add_tze_input<WB>(
+        &mut self,
+        extension_id: ExtensionId,
+        mode: Mode,
+        prevout: (OutPoint, TzeOut),
+        witness_builder: WB,
+    ) -> Result<(), Error<E>>
+    where
+        WB: FnOnce(&BuildCtx, &[TzeOut]) -> Result<Data, E> + 'a,
+    {
+        let (outpoint, coin) = prevout;
+        self.input_total = (self.input_total + coin.value).map_err(Error::Amount)?;
+        self.bundle.inputs.push(TzeIn {
+            prevout: outpoint,
+            witness: (),
+        });
+        self.witness_builders.push(WitnessBuilder {
+            extension_id,
+            mode,
+            build: Box::new(witness_builder),
+        });
+        Ok(())
+    }
+
+    /// Adds a TZE output of `amount` guarded by `precondition`.
+    pub fn add_tze_output<P: ToPayload>(
+        &mut self,
+        extension_id: ExtensionId,
+        amount: Amount<NonNegative>,
+        precondition: &P,
+    ) -> Result<(), Error<E>> {
+        self.output_total = (self.output_total + amount).map_err(Error::Amount)?;
+        self.bundle.outputs.push(TzeOut {
+            value: amount,
+            precondition: precondition.to_data(extension_id),
+        });
+        Ok(())
+    }
+
+    /// Returns the running input value balance minus the output value balance,
+    /// from which the caller can compute the fee this bundle contributes.
+    pub fn value_balance(&self) -> Result<Amount<NegativeAllowed>, Error<E>> {
+        (self.input_total.constrain::<NegativeAllowed>().map_err(Error::Amount)?
+            - self.output_total.constrain::<NegativeAllowed>().map_err(Error::Amount)?)
+        .map_err(Error::Amount)
+    }
+
+    /// Runs every deferred witness closure and returns the fully witnessed
+    /// [`Authorized`] bundle.
+    pub fn build(self) -> Result<Bundle<Authorized>, Error<E>> {
+        let ExtensionTxBuilder {
+            build_ctx,
+            bundle,
+            witness_builders,
+            ..
+        } = self;
+
+        let outputs = bundle.outputs;
+        let mut inputs = Vec::with_capacity(bundle.inputs.len());
+        for (input, witness_builder) in bundle.inputs.into_iter().zip(witness_builders) {
+            let WitnessBuilder {
+                extension_id,
+                mode,
+                build,
+            } = witness_builder;
+            let witness = build(build_ctx, &outputs).map_err(Error::Witness)?;
+            if witness.extension_id != extension_id || witness.mode != mode {
+                return Err(Error::WitnessMismatch {
+                    declared: (extension_id, mode),
+                    produced: (witness.extension_id, witness.mode),
+                });
+            }
+            inputs.push(TzeIn {
+                prevout: input.prevout,
+                witness,
+            });
+        }
+
+        Ok(Bundle {
+            inputs,
+            outputs,
+            authorization: Authorized,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use zebra_chain::{amount::NegativeAllowed, transaction};
+
+    use crate::demo::{DemoExtension, Precondition, Witness, DEMO_EXTENSION_ID};
+    use crate::extension::FromPayload;
+
+    use super::*;
+
+    const DEMO_ID: ExtensionId = ExtensionId(DEMO_EXTENSION_ID);
+
+    fn amount(value: i64) -> Amount<NonNegative> {
+        Amount::try_from(value).expect("value is in range")
+    }
+
+    fn outpoint() -> OutPoint {
+        OutPoint {
+            hash: transaction::Hash([0; 32]),
+            index: 0,
+        }
+    }
+
+    /// Builds a dual-hash-lock bundle: the witness closure reads `hash_2` back
+    /// out of the sibling mode-1 output added to the same bundle, which is only
+    /// possible because the closure runs after every output is known.
+    #[test]
+    fn builds_chained_dual_hash_lock_bundle() {
+        let preimage_1 = [1u8; 32];
+        let preimage_2 = [2u8; 32];
+        let hash_2 = DemoExtension::close_lock(&preimage_2);
+        let hash_1 = DemoExtension::open_lock(&preimage_1, &hash_2);
+
+        let prevout = TzeOut {
+            value: amount(100),
+            precondition: Precondition::Open { hash_1 }.to_data(DEMO_ID),
+        };
+
+        let ctx = ();
+        let mut builder: ExtensionTxBuilder<(), Infallible> = ExtensionTxBuilder::new(&ctx);
+        builder
+            .add_tze_output(DEMO_ID, amount(30), &Precondition::Close { hash_2 })
+            .expect("output value in range");
+        builder
+            .add_tze_input(DEMO_ID, Mode(0), (outpoint(), prevout), |_ctx, outputs| {
+                // A chained extension references the sibling close output.
+                let sibling = outputs.iter().find_map(|out| {
+                    match Precondition::from_payload(out.precondition.mode, &out.precondition.payload) {
+                        Ok(Precondition::Close { hash_2 }) => Some(hash_2),
+                        _ => None,
+                    }
+                });
+                assert_eq!(sibling, Some(hash_2), "closure sees the sibling output");
+                Ok(Witness::Open { preimage_1 }.to_data(DEMO_ID))
+            })
+            .expect("input value in range");
+
+        assert_eq!(
+            builder.value_balance().expect("balance in range"),
+            Amount::<NegativeAllowed>::try_from(70).expect("balance in range")
+        );
+
+        let bundle = builder.build().expect("witness closure succeeds");
+        assert_eq!(bundle.inputs.len(), 1);
+        assert_eq!(bundle.outputs.len(), 1);
+        assert_eq!(bundle.inputs[0].witness.extension_id, DEMO_ID);
+        assert_eq!(bundle.inputs[0].witness.mode, Mode(0));
+    }
+
+    /// A closure that stamps the wrong `(extension_id, mode)` is rejected by
+    /// [`build`](ExtensionTxBuilder::build).
+    #[test]
+    fn wrong_witness_stamp_is_rejected() {
+        let prevout = TzeOut {
+            value: amount(10),
+            precondition: Precondition::Open { hash_1: [0; 32] }.to_data(DEMO_ID),
+        };
+
+        let ctx = ();
+        let mut builder: ExtensionTxBuilder<(), Infallible> = ExtensionTxBuilder::new(&ctx);
+        builder
+            .add_tze_input(DEMO_ID, Mode(0), (outpoint(), prevout), |_ctx, _outputs| {
+                Ok(Witness::Open { preimage_1: [0; 32] }.to_data(ExtensionId(0x9999)))
+            })
+            .expect("input value in range");
+
+        assert!(matches!(
+            builder.build(),
+            Err(Error::WitnessMismatch {
+                declared: (DEMO_ID, Mode(0)),
+                produced: (ExtensionId(0x9999), Mode(0)),
+            })
+        ));
+    }
+}
@@ -0,0 +1,373 @@
+//! Reference dual-hash-lock demo extension.
+//!
+//! This is a cheap, deterministic extension that exercises the [`Extension`]
+//! and [`Registry`](crate::Registry) machinery — in particular cross-transaction
+//! mode chaining — without the cost of a real proof system. It ports the
+//! two-mode dual-hash-lock from the librustzcash demo:
+//!
+//! * A mode-0 ("open") output commits to `hash_1`. Spending it requires a
+//!   mode-0 witness `preimage_1` such that
+//!   `BLAKE2b-256(preimage_1 || hash_2) == hash_1`, where `hash_2` is read from
+//!   the precondition of a mode-1 output created in the *same* spending
+//!   transaction.
+//! * A mode-1 ("close") output commits to `hash_2`. Spending it requires a
+//!   mode-1 witness `preimage_2` such that `BLAKE2b-256(preimage_2) == hash_2`.
+
+use blake2b_simd::Params;
+
+use zebra_chain::tze::{ExtensionId, Mode, TzeOut};
+
+use crate::extension::{Extension, FromPayload, ToPayload};
+use crate::verify::VerifyContext;
+
+/// Extension identifier allocated to the demo extension (`"DEMO"` in ASCII).
+pub const DEMO_EXTENSION_ID: u64 = 0x4445_4D4F;
+
+/// Supported TZE modes for the demo extension.
+pub const DEMO_SUPPORTED_MODES: &[u64] = &[MODE_OPEN, MODE_CLOSE];
+
+/// The "open" mode: a hash lock closed by a preimage and a chained `hash_2`.
+const MODE_OPEN: u64 = 0;
+/// The "close" mode: a plain hash lock closed by a preimage.
+const MODE_CLOSE: u64 = 1;
+
+/// Fixed personalization for the demo's BLAKE2b-256 instance (exactly 16 bytes).
+const PERSONALIZATION: &[u8; 16] = b"ZebraTZEDemoHash";
+
+/// Length in bytes of every demo hash, preimage, precondition and witness payload.
+const HASH_SIZE: usize = 32;
+
+/// Errors returned by the demo extension.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DemoError {
+    /// A payload was not exactly [`HASH_SIZE`] bytes.
+    InvalidPayloadLength(usize),
+    /// A mode outside [`DEMO_SUPPORTED_MODES`] was requested.
+    UnsupportedMode(u64),
+    /// The precondition and witness modes did not agree.
+    ModeMismatch,
+    /// A mode-0 spend found no mode-1 demo output to supply `hash_2`.
+    MissingCloseOutput,
+    /// The supplied preimage did not hash to the committed value.
+    HashMismatch,
+}
+
+impl std::fmt::Display for DemoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DemoError::InvalidPayloadLength(len) => {
+                write!(f, "demo payload must be {HASH_SIZE} bytes, got {len}")
+            }
+            DemoError::UnsupportedMode(mode) => write!(f, "unsupported demo mode {mode}"),
+            DemoError::ModeMismatch => write!(f, "precondition and witness modes disagree"),
+            DemoError::MissingCloseOutput => {
+                write!(f, "mode-0 spend has no mode-1 output to supply hash_2")
+            }
+            DemoError::HashMismatch => write!(f, "preimage does not satisfy the hash lock"),
+        }
+    }
+}
+
+impl std::error::Error for DemoError {}
+
+/// A demo precondition, tagged by the mode that produced it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Precondition {
+    /// Mode-0 precondition: the chained hash lock `hash_1`.
+    Open { hash_1: [u8; HASH_SIZE] },
+    /// Mode-1 precondition: the plain hash lock `hash_2`.
+    Close { hash_2: [u8; HASH_SIZE] },
+}
+
+/// A demo witness, tagged by the mode that produced it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Witness {
+    /// Mode-0 witness: the preimage `preimage_1`.
+    Open { preimage_1: [u8; HASH_SIZE] },
+    /// Mode-1 witness: the preimage `preimage_2`.
+    Close { preimage_2: [u8; HASH_SIZE] },
+}
+
+/// Reads a fixed-size payload, rejecting any other length.
+fn read_hash(payload: &[u8]) -> Result<[u8; HASH_SIZE], DemoError> {
+    payload
+        .try_into()
+        .map_err(|_| DemoError::InvalidPayloadLength(payload.len()))
+}
+
+impl FromPayload for Precondition {
+    type Error = DemoError;
+
+    fn from_payload(mode: Mode, payload: &[u8]) -> Result<Self, Self::Error> {
+        let hash = read_hash(payload)?;
+        match mode.0 {
+            MODE_OPEN => Ok(Precondition::Open { hash_1: hash }),
+            MODE_CLOSE => Ok(Precondition::Close { hash_2: hash }),
+            other => Err(DemoError::UnsupportedMode(other)),
+        }
+    }
+}
+
+impl ToPayload for Precondition {
+    fn to_payload(&self) -> (Mode, Vec<u8>) {
+        match self {
+            Precondition::Open { hash_1 } => (Mode(MODE_OPEN), hash_1.to_vec()),
+            Precondition::Close { hash_2 } => (Mode(MODE_CLOSE), hash_2.to_vec()),
+        }
+    }
+}
+
+impl FromPayload for Witness {
+    type Error = DemoError;
+
+    fn from_payload(mode: Mode, payload: &[u8]) -> Result<Self, Self::Error> {
+        let preimage = read_hash(payload)?;
+        match mode.0 {
+            MODE_OPEN => Ok(Witness::Open {
+                preimage_1: preimage,
+            }),
+            MODE_CLOSE => Ok(Witness::Close {
+                preimage_2: preimage,
+            }),
+            other => Err(DemoError::UnsupportedMode(other)),
+        }
+    }
+}
+
+impl ToPayload for Witness {
+    fn to_payload(&self) -> (Mode, Vec<u8>) {
+        match self {
+            Witness::Open { preimage_1 } => (Mode(MODE_OPEN), preimage_1.to_vec()),
+            Witness::Close { preimage_2 } => (Mode(MODE_CLOSE), preimage_2.to_vec()),
+        }
+    }
+}
+
+/// The dual-hash-lock demo extension.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DemoExtension;
+
+impl DemoExtension {
+    /// Computes the mode-1 ("close") lock `hash_2 = BLAKE2b-256(preimage_2)`.
+    ///
+    /// Callers assembling a dual-hash-lock bundle use this to fill a mode-1
+    /// precondition and [`open_lock`](Self::open_lock) for the chained mode-0 one.
+    pub fn close_lock(preimage_2: &[u8; HASH_SIZE]) -> [u8; HASH_SIZE] {
+        Self::hash(&[preimage_2])
+    }
+
+    /// Computes the mode-0 ("open") lock `hash_1 = BLAKE2b-256(preimage_1 || hash_2)`.
+    pub fn open_lock(preimage_1: &[u8; HASH_SIZE], hash_2: &[u8; HASH_SIZE]) -> [u8; HASH_SIZE] {
+        Self::hash(&[preimage_1, hash_2])
+    }
+
+    /// Computes `BLAKE2b-256` over the concatenation of `parts` using the demo's
+    /// fixed personalization.
+    fn hash(parts: &[&[u8]]) -> [u8; HASH_SIZE] {
+        let mut state = Params::new()
+            .hash_length(HASH_SIZE)
+            .personal(PERSONALIZATION)
+            .to_state();
+        for part in parts {
+            state.update(part);
+        }
+        let mut output = [0u8; HASH_SIZE];
+        output.copy_from_slice(state.finalize().as_bytes());
+        output
+    }
+}
+
+impl Extension<VerifyContext<'_>> for DemoExtension {
+    type Precondition = Precondition;
+    type Witness = Witness;
+    type Error = DemoError;
+
+    fn verify_inner(
+        &self,
+        precondition: &Self::Precondition,
+        witness: &Self::Witness,
+        context: &VerifyContext<'_>,
+    ) -> Result<(), Self::Error> {
+        Self::verify_locks(precondition, witness, context.tze_outputs())
+    }
+}
+
+impl DemoExtension {
+    /// Verifies the dual-hash-lock against the TZE `outputs` of the spending
+    /// transaction, resolving a mode-0 spend's `hash_2` from a sibling mode-1
+    /// output. Split out from [`verify_inner`](Extension::verify_inner) so the
+    /// chaining logic can be exercised without a full [`VerifyContext`].
+    fn verify_locks(
+        precondition: &Precondition,
+        witness: &Witness,
+        outputs: &[TzeOut],
+    ) -> Result<(), DemoError> {
+        match (precondition, witness) {
+            (Precondition::Open { hash_1 }, Witness::Open { preimage_1 }) => {
+                let hash_2 = find_close_hash(outputs).ok_or(DemoError::MissingCloseOutput)?;
+                if &Self::hash(&[preimage_1, &hash_2]) == hash_1 {
+                    Ok(())
+                } else {
+                    Err(DemoError::HashMismatch)
+                }
+            }
+            (Precondition::Close { hash_2 }, Witness::Close { preimage_2 }) => {
+                if &Self::hash(&[preimage_2]) == hash_2 {
+                    Ok(())
+                } else {
+                    Err(DemoError::HashMismatch)
+                }
+            }
+            _ => Err(DemoError::ModeMismatch),
+        }
+    }
+}
+
+/// Returns `hash_2` from the first mode-1 demo output in `outputs`, if any.
+fn find_close_hash(outputs: &[TzeOut]) -> Option<[u8; HASH_SIZE]> {
+    outputs
+        .iter()
+        .filter(|out| out.precondition.extension_id == ExtensionId(DEMO_EXTENSION_ID))
+        .find_map(|out| match Precondition::from_payload(out.precondition.mode, &out.precondition.payload) {
+            Ok(Precondition::Close { hash_2 }) => Some(hash_2),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use zebra_chain::{
+        amount::Amount,
+        block::Height,
+        parameters::ConsensusBranchId,
+        tze::Data,
+    };
+
+    use crate::extension::Extension;
+    use crate::verify::VerifyContext;
+
+    use super::*;
+
+    /// Wraps `precondition` in a `TzeOut` the way the builder would.
+    fn demo_output(precondition: &Precondition) -> TzeOut {
+        TzeOut {
+            value: Amount::zero(),
+            precondition: precondition.to_data(ExtensionId(DEMO_EXTENSION_ID)),
+        }
+    }
+
+    /// Builds a [`VerifyContext`] over `prevout`, with `tze_outputs` supplying
+    /// the siblings a mode-0 spend chains through. The spending transaction is
+    /// left unset, exercising the decode → verify path in isolation.
+    fn context<'a>(prevout: &'a TzeOut, tze_outputs: &'a [TzeOut]) -> VerifyContext<'a> {
+        VerifyContext {
+            transaction: None,
+            tze_in_index: 0,
+            prevout,
+            tze_outputs,
+            consensus_branch_id: ConsensusBranchId::default(),
+            height: Height(0),
+        }
+    }
+
+    /// Drives [`Extension::verify`] end-to-end from raw [`Data`] payloads.
+    fn verify(precondition: &Data, witness: &Data, ctx: &VerifyContext<'_>) -> Result<(), DemoError> {
+        Extension::verify(&DemoExtension, precondition, witness, ctx)
+    }
+
+    /// A mode-0 spend decodes its payloads, reads `hash_2` from a sibling mode-1
+    /// output, and succeeds when `BLAKE2b-256(preimage_1 || hash_2) == hash_1`.
+    #[test]
+    fn open_spend_chains_through_close_output() {
+        let preimage_1 = [1u8; HASH_SIZE];
+        let preimage_2 = [2u8; HASH_SIZE];
+        let hash_2 = DemoExtension::close_lock(&preimage_2);
+        let hash_1 = DemoExtension::open_lock(&preimage_1, &hash_2);
+
+        let id = ExtensionId(DEMO_EXTENSION_ID);
+        let prevout = demo_output(&Precondition::Open { hash_1 });
+        let outputs = [demo_output(&Precondition::Close { hash_2 })];
+        let witness = Witness::Open { preimage_1 }.to_data(id);
+
+        assert_eq!(
+            verify(&prevout.precondition, &witness, &context(&prevout, &outputs)),
+            Ok(())
+        );
+    }
+
+    /// A mode-0 spend with no mode-1 output cannot resolve `hash_2`.
+    #[test]
+    fn open_spend_without_close_output_fails() {
+        let id = ExtensionId(DEMO_EXTENSION_ID);
+        let prevout = demo_output(&Precondition::Open {
+            hash_1: [0u8; HASH_SIZE],
+        });
+        let witness = Witness::Open {
+            preimage_1: [1u8; HASH_SIZE],
+        }
+        .to_data(id);
+
+        assert_eq!(
+            verify(&prevout.precondition, &witness, &context(&prevout, &[])),
+            Err(DemoError::MissingCloseOutput)
+        );
+    }
+
+    /// A plain mode-1 spend succeeds on `BLAKE2b-256(preimage_2) == hash_2`.
+    #[test]
+    fn close_spend_verifies_plain_hash_lock() {
+        let preimage_2 = [7u8; HASH_SIZE];
+        let hash_2 = DemoExtension::close_lock(&preimage_2);
+
+        let id = ExtensionId(DEMO_EXTENSION_ID);
+        let prevout = demo_output(&Precondition::Close { hash_2 });
+        let witness = Witness::Close { preimage_2 }.to_data(id);
+
+        assert_eq!(
+            verify(&prevout.precondition, &witness, &context(&prevout, &[])),
+            Ok(())
+        );
+    }
+
+    /// Mismatched input and witness modes are rejected after decoding.
+    #[test]
+    fn mode_mismatch_is_rejected() {
+        let id = ExtensionId(DEMO_EXTENSION_ID);
+        let prevout = demo_output(&Precondition::Open {
+            hash_1: [0u8; HASH_SIZE],
+        });
+        let witness = Witness::Close {
+            preimage_2: [0u8; HASH_SIZE],
+        }
+        .to_data(id);
+
+        assert_eq!(
+            verify(&prevout.precondition, &witness, &context(&prevout, &[])),
+            Err(DemoError::ModeMismatch)
+        );
+    }
+
+    /// Payloads must be exactly [`HASH_SIZE`] bytes, and the codecs round-trip.
+    #[test]
+    fn payloads_enforce_length_and_round_trip() {
+        assert_eq!(
+            Precondition::from_payload(Mode(MODE_OPEN), &[0u8; 31]),
+            Err(DemoError::InvalidPayloadLength(31))
+        );
+
+        let precondition = Precondition::Close {
+            hash_2: [9u8; HASH_SIZE],
+        };
+        let (mode, payload) = precondition.to_payload();
+        assert_eq!(
+            Precondition::from_payload(mode, &payload),
+            Ok(precondition)
+        );
+
+        let witness = Witness::Open {
+            preimage_1: [4u8; HASH_SIZE],
+        };
+        let (mode, payload) = witness.to_payload();
+        assert_eq!(Witness::from_payload(mode, &payload), Ok(witness));
+    }
+}
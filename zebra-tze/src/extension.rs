@@ -0,0 +1,105 @@
+//! The [`Extension`] trait and the payload codecs it builds on.
+
+use zebra_chain::tze::{Data, Mode};
+
+/// Boxed error used by the object-safe [`Verifier`] interface.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Decodes a typed value from the raw bytes of a [`Data`] payload.
+///
+/// The `mode` selects how the `payload` bytes are interpreted, mirroring
+/// librustzcash's `extensions::transparent::FromPayload`. Implementations are
+/// expected to reject modes they do not support rather than silently accepting
+/// them.
+pub trait FromPayload: Sized {
+    /// Error returned when the payload cannot be decoded under `mode`.
+    type Error;
+
+    /// Decodes a value from `payload` interpreted under `mode`.
+    fn from_payload(mode: Mode, payload: &[u8]) -> Result<Self, Self::Error>;
+}
+
+/// Encodes a typed value into the `(mode, payload)` parts of a [`Data`].
+///
+/// [`to_payload`](ToPayload::to_payload) must round-trip through
+/// [`FromPayload`]: decoding the `mode` and bytes it produces yields an equal
+/// value. Re-encoding a decoded value need not reproduce the original bytes when
+/// a codec also accepts other encodings of the same value.
+pub trait ToPayload {
+    /// Encodes the value into its `(mode, payload)` representation.
+    fn to_payload(&self) -> (Mode, Vec<u8>);
+
+    /// Builds a [`Data`] for `extension_id` from this value.
+    fn to_data(&self, extension_id: zebra_chain::tze::ExtensionId) -> Data {
+        let (mode, payload) = self.to_payload();
+        Data {
+            extension_id,
+            mode,
+            payload,
+        }
+    }
+}
+
+/// A transparent extension: the typed verifier behind a single
+/// [`ExtensionId`](zebra_chain::tze::ExtensionId).
+///
+/// The context type `C` carries whatever surrounding state the verifier needs —
+/// for the consensus path this is the spending transaction and the referenced
+/// output (see [`VerifyContext`](crate::verify::VerifyContext)).
+pub trait Extension<C> {
+    /// Typed precondition committed by a `TzeOut`.
+    type Precondition;
+    /// Typed witness supplied by a spending `TzeIn`.
+    type Witness;
+    /// Error returned when verification or payload decoding fails.
+    type Error;
+
+    /// Verifies a decoded `witness` against a decoded `precondition`.
+    fn verify_inner(
+        &self,
+        precondition: &Self::Precondition,
+        witness: &Self::Witness,
+        context: &C,
+    ) -> Result<(), Self::Error>;
+
+    /// Decodes the raw payloads and dispatches to [`verify_inner`](Extension::verify_inner).
+    fn verify(
+        &self,
+        precondition: &Data,
+        witness: &Data,
+        context: &C,
+    ) -> Result<(), Self::Error>
+    where
+        Self::Precondition: FromPayload<Error = Self::Error>,
+        Self::Witness: FromPayload<Error = Self::Error>,
+    {
+        self.verify_inner(
+            &Self::Precondition::from_payload(precondition.mode, &precondition.payload)?,
+            &Self::Witness::from_payload(witness.mode, &witness.payload)?,
+            context,
+        )
+    }
+}
+
+/// Object-safe view of an [`Extension`] used by the [`Registry`].
+///
+/// [`Extension`] has associated types and so cannot be stored as a trait object
+/// directly; this trait erases them behind [`Data`] and [`BoxError`]. It is
+/// implemented automatically for every [`Extension`] whose payloads decode with
+/// its own error type.
+pub trait Verifier<C> {
+    /// Verifies `witness` against `precondition` in `context`.
+    fn verify(&self, precondition: &Data, witness: &Data, context: &C) -> Result<(), BoxError>;
+}
+
+impl<C, E> Verifier<C> for E
+where
+    E: Extension<C>,
+    E::Precondition: FromPayload<Error = E::Error>,
+    E::Witness: FromPayload<Error = E::Error>,
+    E::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn verify(&self, precondition: &Data, witness: &Data, context: &C) -> Result<(), BoxError> {
+        Extension::verify(self, precondition, witness, context).map_err(|err| Box::new(err) as BoxError)
+    }
+}
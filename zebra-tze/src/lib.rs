@@ -0,0 +1,24 @@
+//! Generic Transparent Zcash Extension (TZE) verifier framework.
+//!
+//! [`zebra_chain::tze`] defines the on-wire [`Data`](zebra_chain::tze::Data)
+//! payload carried by TZE inputs and outputs. This crate adds the typed layer on
+//! top: an [`Extension`] trait (modeled on librustzcash's
+//! `extensions::transparent::Extension`) that decodes those payloads into mode
+//! specific precondition and witness values, and a [`Registry`] that dispatches
+//! to the right extension by [`ExtensionId`](zebra_chain::tze::ExtensionId).
+//!
+//! Concrete verifiers — such as the STWO-Cairo extension in `zebra-tze-stwo` —
+//! implement [`Extension`] and register themselves in a [`Registry`], so the
+//! consensus path can verify an input without knowing which extension produced
+//! it.
+
+pub mod builder;
+pub mod demo;
+pub mod extension;
+pub mod registry;
+pub mod verify;
+
+pub use builder::ExtensionTxBuilder;
+pub use extension::{Extension, FromPayload, ToPayload, Verifier};
+pub use registry::Registry;
+pub use verify::{verify_bundle, VerifyContext};
@@ -0,0 +1,61 @@
+//! The [`Registry`] mapping [`ExtensionId`]s to their verifiers.
+
+use std::collections::HashMap;
+
+use zebra_chain::tze::{Data, ExtensionId};
+
+use crate::extension::{BoxError, Verifier};
+
+/// A set of registered TZE verifiers, keyed by [`ExtensionId`].
+///
+/// The consensus path builds a registry once, registering every extension it
+/// understands, and then dispatches each `TzeIn` through
+/// [`verify`](Registry::verify) without needing to know which extension applies.
+pub struct Registry<C> {
+    extensions: HashMap<ExtensionId, Box<dyn Verifier<C>>>,
+}
+
+impl<C> Default for Registry<C> {
+    fn default() -> Self {
+        Registry {
+            extensions: HashMap::new(),
+        }
+    }
+}
+
+impl<C> Registry<C> {
+    /// Returns an empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Registers `extension` under `id`, returning the verifier it replaced, if any.
+    pub fn register(
+        &mut self,
+        id: ExtensionId,
+        extension: Box<dyn Verifier<C>>,
+    ) -> Option<Box<dyn Verifier<C>>> {
+        self.extensions.insert(id, extension)
+    }
+
+    /// Returns the verifier registered under `id`, if any.
+    pub fn get(&self, id: &ExtensionId) -> Option<&dyn Verifier<C>> {
+        self.extensions.get(id).map(AsRef::as_ref)
+    }
+
+    /// Dispatches verification of `witness` against `precondition` to the
+    /// extension registered under `id`.
+    ///
+    /// Returns [`None`] when no extension is registered for `id`; otherwise the
+    /// inner result is the extension's verdict.
+    pub fn verify(
+        &self,
+        id: &ExtensionId,
+        precondition: &Data,
+        witness: &Data,
+        context: &C,
+    ) -> Option<Result<(), BoxError>> {
+        self.get(id)
+            .map(|extension| extension.verify(precondition, witness, context))
+    }
+}
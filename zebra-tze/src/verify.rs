@@ -0,0 +1,167 @@
+//! Consensus integration: [`VerifyContext`] and [`verify_bundle`].
+
+use zebra_chain::{
+    block::Height,
+    parameters::ConsensusBranchId,
+    tze::{Authorized, Bundle, ExtensionId, OutPoint, TzeOut},
+    transaction::Transaction,
+};
+
+use crate::extension::BoxError;
+use crate::registry::Registry;
+
+/// Everything an extension verifier may inspect about the transaction spending a
+/// `TzeIn`.
+///
+/// The `tze_outputs` let verifiers reach sibling `TzeOut`s created in the same
+/// transaction (as mode chaining requires); `prevout` is the committed output
+/// being spent, resolved from the input's [`OutPoint`]. The full spending
+/// `transaction` is also carried for verifiers that need more than the TZE
+/// outputs.
+pub struct VerifyContext<'a> {
+    /// The transaction spending the TZE input under evaluation, when the
+    /// verifier runs inside one. It is `None` for callers that verify a bundle
+    /// in isolation and supply [`tze_outputs`](Self::tze_outputs) directly.
+    pub transaction: Option<&'a Transaction>,
+    /// Index of the `TzeIn` being evaluated within the bundle.
+    pub tze_in_index: usize,
+    /// The committed `TzeOut` referenced by the input's prevout.
+    pub prevout: &'a TzeOut,
+    /// The TZE outputs created by the spending transaction, used for mode
+    /// chaining. [`verify_bundle`] populates these from the bundle under test.
+    pub tze_outputs: &'a [TzeOut],
+    /// Consensus branch id in effect for the spending transaction.
+    pub consensus_branch_id: ConsensusBranchId,
+    /// Height of the block containing the spending transaction.
+    pub height: Height,
+}
+
+impl VerifyContext<'_> {
+    /// Returns the TZE outputs created by the spending transaction.
+    pub fn tze_outputs(&self) -> &[TzeOut] {
+        self.tze_outputs
+    }
+}
+
+/// Errors returned by [`verify_bundle`], each naming the failing outpoint.
+#[derive(Debug)]
+pub enum Error {
+    /// No committed output was supplied for the input's prevout.
+    UnknownPrevout {
+        /// The input's prevout that could not be resolved.
+        outpoint: OutPoint,
+    },
+    /// The precondition and witness disagreed on extension id or mode.
+    Mismatch {
+        /// The input's prevout.
+        outpoint: OutPoint,
+    },
+    /// No extension was registered for the precondition's extension id.
+    UnregisteredExtension {
+        /// The input's prevout.
+        outpoint: OutPoint,
+        /// The extension id that had no registered verifier.
+        extension_id: ExtensionId,
+    },
+    /// The registered extension rejected the witness.
+    VerificationFailed {
+        /// The input's prevout.
+        outpoint: OutPoint,
+        /// The extension's error.
+        source: BoxError,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownPrevout { outpoint } => {
+                write!(f, "no committed output for prevout {outpoint:?}")
+            }
+            Error::Mismatch { outpoint } => write!(
+                f,
+                "precondition and witness extension/mode disagree for prevout {outpoint:?}"
+            ),
+            Error::UnregisteredExtension {
+                outpoint,
+                extension_id,
+            } => write!(
+                f,
+                "no extension registered for id {:#x} spending prevout {outpoint:?}",
+                extension_id.0
+            ),
+            Error::VerificationFailed { outpoint, source } => {
+                write!(f, "extension rejected prevout {outpoint:?}: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::VerificationFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Verifies every `TzeIn` of `bundle` against its committed precondition.
+///
+/// `prevouts[i]` must be the `TzeOut` referenced by `bundle.inputs[i]`'s prevout.
+/// For each input this checks `extension_id`/`mode` agreement between the
+/// committed precondition and the supplied witness, then dispatches through
+/// `registry`. Errors name the failing outpoint.
+pub fn verify_bundle<'a>(
+    bundle: &'a Bundle<Authorized>,
+    transaction: Option<&'a Transaction>,
+    prevouts: &'a [TzeOut],
+    consensus_branch_id: ConsensusBranchId,
+    height: Height,
+    registry: &Registry<VerifyContext<'a>>,
+) -> Result<(), Error> {
+    for (index, input) in bundle.inputs.iter().enumerate() {
+        let prevout = prevouts
+            .get(index)
+            .ok_or(Error::UnknownPrevout {
+                outpoint: input.prevout,
+            })?;
+        let precondition = &prevout.precondition;
+        let witness = &input.witness;
+
+        if precondition.extension_id != witness.extension_id
+            || precondition.mode != witness.mode
+        {
+            return Err(Error::Mismatch {
+                outpoint: input.prevout,
+            });
+        }
+
+        let context = VerifyContext {
+            transaction,
+            tze_in_index: index,
+            prevout,
+            tze_outputs: &bundle.outputs,
+            consensus_branch_id,
+            height,
+        };
+
+        match registry.verify(&precondition.extension_id, precondition, witness, &context) {
+            Some(Ok(())) => {}
+            Some(Err(source)) => {
+                return Err(Error::VerificationFailed {
+                    outpoint: input.prevout,
+                    source,
+                })
+            }
+            None => {
+                return Err(Error::UnregisteredExtension {
+                    outpoint: input.prevout,
+                    extension_id: precondition.extension_id,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}